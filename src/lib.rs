@@ -30,10 +30,10 @@ use std::collections::HashMap;
 /// Add a data type `ByteCode` that can represent bytecode like in the example
 /// above, along with an interpreter for said bytecode. Make sure your bytecode
 /// is flat, i.e. not nested.
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ByteCode<'a> {
-    /// Unary operation: load a number to the number stack
-    LoadVal(i32),
+    /// Unary operation: load a value to the number stack
+    LoadVal(Value),
     /// Unary operation: bind the loaded in the stack value to the variable
     WriteVar(&'a str),
     /// Unary operation: load the value binded to the variable to the number stack
@@ -52,14 +52,122 @@ pub enum ByteCode<'a> {
     Loop,
     /// Null operation: marks the end of the current loop
     EndLoop,
+    /// Control flow: unconditionally set the program counter to the absolute instruction index
+    Jump(usize),
+    /// Control flow: pop the top of the number stack and jump to the absolute index if it equals 0
+    JumpIfZero(usize),
+    /// Control flow: pop the top of the number stack and jump to the absolute index if it is not 0
+    JumpIfNonZero(usize),
+    /// Comparison: pop the 2 last numbers and push `b == a` onto the boolean stack
+    Equal,
+    /// Comparison: pop the 2 last numbers and push `b != a` onto the boolean stack
+    NotEqual,
+    /// Comparison: pop the 2 last numbers and push `b < a` onto the boolean stack
+    Less,
+    /// Comparison: pop the 2 last numbers and push `b > a` onto the boolean stack
+    Greater,
+    /// Comparison: pop the 2 last numbers and push `b <= a` onto the boolean stack
+    LessEqual,
+    /// Comparison: pop the 2 last numbers and push `b >= a` onto the boolean stack
+    GreaterEqual,
+    /// Logical: pop the 2 last booleans and push their conjunction
+    And,
+    /// Logical: pop the 2 last booleans and push their disjunction
+    Or,
+    /// Logical: pop the last boolean and push its negation
+    Not,
+    /// Control flow: pop the top of the boolean stack and jump to the absolute index if it is false
+    JumpIfFalse(usize),
+    /// Control flow: push a new call frame, move `arity` arguments from the caller's number stack
+    /// into it, and jump to the function's absolute instruction index
+    Call { target: usize, arity: usize },
+    /// Control flow: pop the current call frame and resume at the caller's saved program counter,
+    /// leaving the callee's top value on the caller's number stack
+    Return,
+}
+
+impl<'a> ByteCode<'a> {
+    /// Load an integer constant. Preserves the original `LoadVal(1)` ergonomics now that `LoadVal`
+    /// carries a typed [`Value`]: `ByteCode::load_int(1)` reads the same as the pre-`Value` API.
+    #[must_use]
+    pub const fn load_int(number: i32) -> ByteCode<'a> {
+        ByteCode::LoadVal(Value::Int(number as i64))
+    }
+}
+
+/// A call frame owns the variables local to a function invocation and the program counter to
+/// resume from on `Return`. The newest frame is the last element; the first is the top-level code.
+struct Frame<'a> {
+    /// Values loaded and computed within this frame live on its own number stack
+    number_stack: Vec<Value>,
+
+    /// Variables are binded to their value in a HashMap, scoped to this frame
+    variables_map: HashMap<&'a str, Value>,
+
+    /// Program counter to restore in the caller once this frame returns
+    return_pc: usize,
+}
+
+impl<'a> Frame<'a> {
+    fn new(return_pc: usize) -> Self {
+        Frame {
+            number_stack: Vec::with_capacity(20),
+            variables_map: HashMap::new(),
+            return_pc,
+        }
+    }
+}
+
+/// A typed runtime value. Integer and floating point values share the number stack; arithmetic
+/// promotes to `Float` as soon as one operand is a `Float`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl From<i32> for Value {
+    /// Construct an `Int` from a plain integer; see also [`ByteCode::load_int`].
+    fn from(number: i32) -> Self {
+        Value::Int(i64::from(number))
+    }
+}
+
+impl Value {
+    /// Whether the value equals zero, used by the conditional jumps. A boolean has no numeric
+    /// zero, so branching on one is a typed error rather than being silently treated as non-zero.
+    fn is_zero(self) -> Result<bool, &'static str> {
+        match self {
+            Value::Int(number) => Ok(number == 0),
+            Value::Float(number) => Ok(number == 0.0),
+            Value::Bool(_) => Err("Cannot branch on a boolean with JumpIfZero/JumpIfNonZero."),
+        }
+    }
+
+    /// Numeric view of the value for arithmetic and comparisons; booleans have none
+    fn as_f64(self) -> Option<f64> {
+        match self {
+            Value::Int(number) => Some(number as f64),
+            Value::Float(number) => Some(number),
+            Value::Bool(_) => None,
+        }
+    }
 }
 
 pub struct Interpreter<'a> {
-    /// Values are loaded in the number stack
-    number_stack: Vec<i32>,
+    /// Call frames, newest last; `frames[0]` is the top-level code and always present. Each frame
+    /// owns its own number stack so a callee only sees the arguments transferred to it.
+    frames: Vec<Frame<'a>>,
+
+    /// Booleans produced by comparison and logical operations live on their own stack
+    boolean_stack: Vec<bool>,
+
+    /// Active `Loop` iterations as `(remaining_iterations, body_start_pc)`, newest last
+    loop_stack: Vec<(usize, usize)>,
 
-    /// Variables are binded to their value in a HashMap
-    variables_map: HashMap<&'a str, i32>,
+    /// Remaining instruction budget; each dispatched instruction spends one unit
+    fuel: u64,
 }
 
 impl<'a> Default for Interpreter<'a> {
@@ -68,40 +176,133 @@ impl<'a> Default for Interpreter<'a> {
     }
 }
 impl<'a> Interpreter<'a> {
+    /// Default instruction budget, generous enough for honest programs
+    const DEFAULT_FUEL: u64 = 1_000_000;
+
+    /// Hard cap on the number stack to stop a `LoadVal`/`Loop` combination from exhausting memory
+    const MAX_STACK_DEPTH: usize = 1024;
+
     #[must_use]
     pub fn new() -> Interpreter<'static> {
+        Self::with_fuel(Self::DEFAULT_FUEL)
+    }
+
+    /// Build an interpreter with an explicit instruction budget for untrusted programs
+    #[must_use]
+    pub fn with_fuel(fuel: u64) -> Interpreter<'static> {
         Interpreter {
-            number_stack: Vec::with_capacity(20),
-            variables_map: HashMap::new(),
+            frames: vec![Frame::new(0)],
+            boolean_stack: Vec::new(),
+            loop_stack: Vec::new(),
+            fuel,
         }
     }
-    pub fn evaluate(&mut self, bytecodes: &[ByteCode<'a>]) -> Result<i32, &'static str> {
+    pub fn evaluate(&mut self, bytecodes: &[ByteCode<'a>]) -> Result<Value, &'static str> {
         self.evaluate_bytecodes(bytecodes)?;
 
-        match self.number_stack.pop() {
-            Some(number) => Ok(number),
-            None => Err("Incorrectly formatted expression: no return value."), // We arbitrary expect a return value
+        // A program yields the value it left on the number stack, otherwise the top boolean
+        // produced by a comparison or logical operation.
+        if let Some(value) = self.pop_value() {
+            Ok(value)
+        } else if let Some(boolean) = self.boolean_stack.pop() {
+            Ok(Value::Bool(boolean))
+        } else {
+            Err("Incorrectly formatted expression: no return value.") // We arbitrary expect a return value
         }
     }
 
     fn evaluate_bytecodes(&mut self, bytecodes: &[ByteCode<'a>]) -> Result<(), &'static str> {
-        for (bytecode_index, bytecode) in bytecodes.iter().enumerate() {
-            match *bytecode {
-                ByteCode::LoadVal(number) => self.number_stack.push(number),
-                ByteCode::WriteVar(variable) => self.bind_variable(variable)?,
-                ByteCode::ReadVar(variable) => self.read_variable(variable)?,
-                ByteCode::Loop => self.repeat(bytecodes, bytecode_index)?,
-                ByteCode::EndLoop => (),
-                _ => self.binary_calculus(bytecode)?,
+        let mut pc = 0;
+        while pc < bytecodes.len() {
+            if self.fuel == 0 {
+                return Err("execution budget exceeded");
+            }
+            self.fuel -= 1;
+
+            match bytecodes[pc] {
+                ByteCode::LoadVal(value) => {
+                    self.push_value(value)?;
+                    pc += 1;
+                }
+                ByteCode::WriteVar(variable) => {
+                    self.bind_variable(variable)?;
+                    pc += 1;
+                }
+                ByteCode::ReadVar(variable) => {
+                    self.read_variable(variable)?;
+                    pc += 1;
+                }
+                ByteCode::Jump(target) => pc = Self::jump_target(target, bytecodes.len())?,
+                ByteCode::JumpIfZero(target) => {
+                    pc = if self.pop_condition()?.is_zero()? {
+                        Self::jump_target(target, bytecodes.len())?
+                    } else {
+                        pc + 1
+                    };
+                }
+                ByteCode::JumpIfNonZero(target) => {
+                    pc = if self.pop_condition()?.is_zero()? {
+                        pc + 1
+                    } else {
+                        Self::jump_target(target, bytecodes.len())?
+                    };
+                }
+                ByteCode::JumpIfFalse(target) => {
+                    pc = if self.pop_boolean()? {
+                        pc + 1
+                    } else {
+                        Self::jump_target(target, bytecodes.len())?
+                    };
+                }
+                ByteCode::Call { target, arity } => {
+                    pc = self.enter_call(target, arity, bytecodes.len(), pc)?;
+                }
+                ByteCode::Return => pc = self.return_from_call()?,
+                ByteCode::Loop => pc = self.enter_loop(bytecodes, pc)?,
+                ByteCode::EndLoop => pc = self.repeat_loop(pc),
+                ByteCode::Equal
+                | ByteCode::NotEqual
+                | ByteCode::Less
+                | ByteCode::Greater
+                | ByteCode::LessEqual
+                | ByteCode::GreaterEqual => {
+                    self.comparison(&bytecodes[pc])?;
+                    pc += 1;
+                }
+                ByteCode::And | ByteCode::Or | ByteCode::Not => {
+                    self.logical(&bytecodes[pc])?;
+                    pc += 1;
+                }
+                ref bytecode => {
+                    self.binary_calculus(bytecode)?;
+                    pc += 1;
+                }
             }
         }
         Ok(())
     }
 
+    /// Validate an absolute jump target; jumping one past the end terminates the program
+    const fn jump_target(target: usize, length: usize) -> Result<usize, &'static str> {
+        if target > length {
+            Err("Jump target is out of bounds.")
+        } else {
+            Ok(target)
+        }
+    }
+
+    /// Pop the value a conditional jump branches on
+    fn pop_condition(&mut self) -> Result<Value, &'static str> {
+        match self.pop_value() {
+            Some(value) => Ok(value),
+            None => Err("A value is required to branch."),
+        }
+    }
+
     fn bind_variable(&mut self, variable: &'a str) -> Result<(), &'static str> {
-        match self.number_stack.pop() {
+        match self.pop_value() {
             Some(rvalue) => {
-                self.variables_map.insert(variable, rvalue);
+                self.current_frame_mut().variables_map.insert(variable, rvalue);
                 Ok(())
             }
             None => Err("Trying to bind variable without value."),
@@ -109,83 +310,613 @@ impl<'a> Interpreter<'a> {
     }
 
     fn read_variable(&mut self, variable: &'a str) -> Result<(), &'static str> {
-        match self.variables_map.get(variable) {
-            Some(&number) => {
-                self.number_stack.push(number);
-                Ok(())
-            }
+        match self.current_frame().variables_map.get(variable) {
+            Some(&value) => self.push_value(value),
             None => Err("Trying to read variable that does not exist."),
         }
     }
 
-    /// Repeat a set of instructions `x` times where `x` is the top value of the number stack
-    fn repeat(
+    /// The innermost call frame; `frames` always holds at least the top-level frame
+    fn current_frame(&self) -> &Frame<'a> {
+        self.frames.last().expect("the top-level frame is never popped")
+    }
+
+    fn current_frame_mut(&mut self) -> &mut Frame<'a> {
+        self.frames.last_mut().expect("the top-level frame is never popped")
+    }
+
+    /// Enter a `Call`: move the top `arity` values from the caller's number stack into a fresh
+    /// frame and jump to the function body. Returns the program counter to continue from.
+    fn enter_call(
         &mut self,
-        inputs: &[ByteCode<'a>],
+        target: usize,
+        arity: usize,
+        length: usize,
+        pc: usize,
+    ) -> Result<usize, &'static str> {
+        let target = Self::jump_target(target, length)?;
+
+        let caller_stack = &mut self.current_frame_mut().number_stack;
+        if caller_stack.len() < arity {
+            return Err("Not enough arguments for Call.");
+        }
+        let arguments = caller_stack.split_off(caller_stack.len() - arity);
+
+        let mut frame = Frame::new(pc + 1);
+        frame.number_stack = arguments;
+        self.frames.push(frame);
+        Ok(target)
+    }
+
+    /// Pop the current call frame, leave its top value on the caller's number stack, and return the
+    /// caller's saved program counter
+    fn return_from_call(&mut self) -> Result<usize, &'static str> {
+        if self.frames.len() <= 1 {
+            return Err("Return without a matching Call.");
+        }
+        let mut frame = self.frames.pop().expect("checked a frame remains");
+        let return_value = frame.number_stack.pop();
+        if let Some(value) = return_value {
+            self.push_value(value)?;
+        }
+        Ok(frame.return_pc)
+    }
+
+    /// Push a value onto the current frame's number stack, rejecting growth beyond `MAX_STACK_DEPTH`
+    fn push_value(&mut self, value: Value) -> Result<(), &'static str> {
+        let stack = &mut self.current_frame_mut().number_stack;
+        if stack.len() >= Self::MAX_STACK_DEPTH {
+            return Err("maximum stack depth exceeded");
+        }
+        stack.push(value);
+        Ok(())
+    }
+
+    /// Pop a value off the current frame's number stack
+    fn pop_value(&mut self) -> Option<Value> {
+        self.current_frame_mut().number_stack.pop()
+    }
+
+    /// Enter a `Loop`: pop the iteration count and lower the `Loop`/`EndLoop` pair to a
+    /// back-edge jump. Returns the program counter to continue from.
+    fn enter_loop(
+        &mut self,
+        bytecodes: &[ByteCode<'a>],
         bytecode_index: usize,
-    ) -> Result<(), &'static str> {
-        let endloop_bytecode_index = Self::next_endloop_bytecode(&inputs[bytecode_index..])?;
-        let time_number_to_repeat = match self.number_stack.pop() {
-            Some(number) => number as usize,
-            None => return Err("A number is required to use Loop."),
+    ) -> Result<usize, &'static str> {
+        let endloop_bytecode_index = Self::next_endloop_bytecode(bytecodes, bytecode_index)?;
+        let time_number_to_repeat = match self.pop_value() {
+            Some(Value::Int(number)) => number as usize,
+            _ => return Err("A number is required to use Loop."),
         };
 
-        for _ in 0..time_number_to_repeat {
-            self.evaluate_bytecodes(
-                &inputs[bytecode_index + 1..bytecode_index + endloop_bytecode_index],
-            )?;
+        if time_number_to_repeat == 0 {
+            return Ok(endloop_bytecode_index + 1);
         }
 
-        Ok(())
+        let body_start = bytecode_index + 1;
+        self.loop_stack.push((time_number_to_repeat, body_start));
+        Ok(body_start)
+    }
+
+    /// Reach an `EndLoop`: decrement the current loop counter and jump back to the body start
+    /// while iterations remain, otherwise fall through past the `EndLoop`.
+    fn repeat_loop(&mut self, bytecode_index: usize) -> usize {
+        match self.loop_stack.last_mut() {
+            Some((remaining, body_start)) => {
+                *remaining -= 1;
+                if *remaining > 0 {
+                    *body_start
+                } else {
+                    self.loop_stack.pop();
+                    bytecode_index + 1
+                }
+            }
+            None => bytecode_index + 1,
+        }
     }
 
-    /// Given a collection of bytecodes, find the next EndLoop bytecode
-    fn next_endloop_bytecode(bytecodes: &[ByteCode]) -> Result<usize, &'static str> {
-        let endloop_bytecodes = bytecodes
+    /// Given a collection of bytecodes, find the next EndLoop bytecode following `from`
+    fn next_endloop_bytecode(
+        bytecodes: &[ByteCode],
+        from: usize,
+    ) -> Result<usize, &'static str> {
+        match bytecodes
             .iter()
             .enumerate()
-            .filter(|(_, &y)| y == ByteCode::EndLoop)
+            .skip(from)
+            .find(|(_, &y)| y == ByteCode::EndLoop)
             .map(|(x, _)| x)
-            .collect::<Vec<usize>>();
-
-        match endloop_bytecodes.first() {
-            Some(&first_endloop_bytecode_index) => Ok(first_endloop_bytecode_index),
+        {
+            Some(endloop_bytecode_index) => Ok(endloop_bytecode_index),
             None => Err("There is no EndLoop instruction associated to the previous Loop."),
         }
     }
 
-    fn binary_calculus(&mut self, bytecode: &ByteCode) -> Result<(), &'static str> {
-        let first_operand = self.number_stack.pop();
-        let second_operand = self.number_stack.pop();
+    /// Pop the value a `JumpIfFalse` branches on
+    fn pop_boolean(&mut self) -> Result<bool, &'static str> {
+        match self.boolean_stack.pop() {
+            Some(boolean) => Ok(boolean),
+            None => Err("A boolean is required to branch."),
+        }
+    }
+
+    /// Pop the 2 last numbers and push the result of a comparison onto the boolean stack
+    fn comparison(&mut self, bytecode: &ByteCode) -> Result<(), &'static str> {
+        let (lhs, rhs) = match (self.pop_value(), self.pop_value()) {
+            (Some(first), Some(second)) => (second, first),
+            _ => return Err("Incorrectly formatted expression: expecting 2 operands."),
+        };
+
+        // Compare two `Int`s exactly; only promote to `f64` when a `Float` is actually involved,
+        // mirroring how `perform_binary_operation` keeps `Int ⊕ Int` exact.
+        let ordering = match (lhs, rhs) {
+            (Value::Int(lhs), Value::Int(rhs)) => Some(lhs.cmp(&rhs)),
+            (lhs, rhs) => match (lhs.as_f64(), rhs.as_f64()) {
+                (Some(lhs), Some(rhs)) => lhs.partial_cmp(&rhs),
+                _ => return Err("Incorrectly typed operands for comparison."),
+            },
+        };
+
+        use std::cmp::Ordering;
+        let result = match bytecode {
+            ByteCode::Equal => ordering == Some(Ordering::Equal),
+            ByteCode::NotEqual => ordering != Some(Ordering::Equal),
+            ByteCode::Less => ordering == Some(Ordering::Less),
+            ByteCode::Greater => ordering == Some(Ordering::Greater),
+            ByteCode::LessEqual => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            ByteCode::GreaterEqual => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            _ => return Err("Incorrectly formatted expression: expecting a comparison operator."),
+        };
+
+        self.boolean_stack.push(result);
+        Ok(())
+    }
 
-        self.number_stack.push(Self::perform_binary_operation(
-            bytecode,
-            first_operand,
-            second_operand,
-        )?);
+    /// Combine booleans from the boolean stack with a logical operator
+    fn logical(&mut self, bytecode: &ByteCode) -> Result<(), &'static str> {
+        let result = match bytecode {
+            ByteCode::Not => !self.pop_boolean()?,
+            ByteCode::And => {
+                let a = self.pop_boolean()?;
+                let b = self.pop_boolean()?;
+                a && b
+            }
+            ByteCode::Or => {
+                let a = self.pop_boolean()?;
+                let b = self.pop_boolean()?;
+                a || b
+            }
+            _ => return Err("Incorrectly formatted expression: expecting a logical operator."),
+        };
 
+        self.boolean_stack.push(result);
         Ok(())
     }
 
-    const fn perform_binary_operation(
+    fn binary_calculus(&mut self, bytecode: &ByteCode) -> Result<(), &'static str> {
+        let result = match (self.pop_value(), self.pop_value()) {
+            (Some(first_operand), Some(second_operand)) => {
+                Self::perform_binary_operation(bytecode, first_operand, second_operand)?
+            }
+            _ => return Err("Incorrectly formatted expression: expecting 2 operands."),
+        };
+
+        self.push_value(result)
+    }
+
+    /// Apply an arithmetic operator to two values. `int ⊕ int` stays an `Int`; as soon as one
+    /// operand is a `Float` both are promoted to `Float`. Division or modulo by zero is reported
+    /// as a typed error rather than panicking.
+    fn perform_binary_operation(
         bytecode: &ByteCode,
-        first_operand: Option<i32>,
-        second_operand: Option<i32>,
-    ) -> Result<i32, &'static str> {
-        match (bytecode, first_operand, second_operand) {
-            (ByteCode::Add, Some(a), Some(b)) => Ok(b + a),
-            (ByteCode::Subtract, Some(a), Some(b)) => Ok(b - a),
-            (ByteCode::Multiply, Some(a), Some(b)) => Ok(b * a),
-            (ByteCode::Divide, Some(a), Some(b)) => Ok(b / a),
-            (ByteCode::Modulo, Some(a), Some(b)) => Ok(b % a),
-            _ => Err("Incorrectly formatted expression: expecting 2 operands."),
+        first_operand: Value,
+        second_operand: Value,
+    ) -> Result<Value, &'static str> {
+        if let (Value::Int(lhs), Value::Int(rhs)) = (second_operand, first_operand) {
+            return match bytecode {
+                ByteCode::Add => lhs.checked_add(rhs).map(Value::Int).ok_or("arithmetic overflow"),
+                ByteCode::Subtract => {
+                    lhs.checked_sub(rhs).map(Value::Int).ok_or("arithmetic overflow")
+                }
+                ByteCode::Multiply => {
+                    lhs.checked_mul(rhs).map(Value::Int).ok_or("arithmetic overflow")
+                }
+                ByteCode::Divide if rhs == 0 => Err("Attempted to divide by zero."),
+                ByteCode::Divide => Ok(Value::Int(lhs / rhs)),
+                ByteCode::Modulo if rhs == 0 => Err("Attempted to divide by zero."),
+                ByteCode::Modulo => Ok(Value::Int(lhs % rhs)),
+                _ => Err("Incorrectly formatted expression: expecting 2 operands."),
+            };
         }
+
+        let (lhs, rhs) = match (second_operand.as_f64(), first_operand.as_f64()) {
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+            _ => return Err("Incorrectly typed operands for arithmetic operation."),
+        };
+
+        let result = match bytecode {
+            ByteCode::Add => lhs + rhs,
+            ByteCode::Subtract => lhs - rhs,
+            ByteCode::Multiply => lhs * rhs,
+            ByteCode::Divide if rhs == 0.0 => return Err("Attempted to divide by zero."),
+            ByteCode::Divide => lhs / rhs,
+            ByteCode::Modulo if rhs == 0.0 => return Err("Attempted to divide by zero."),
+            ByteCode::Modulo => lhs % rhs,
+            _ => return Err("Incorrectly formatted expression: expecting 2 operands."),
+        };
+
+        // Reject results that overflowed to an infinity rather than leaking a non-finite value.
+        if result.is_finite() {
+            Ok(Value::Float(result))
+        } else {
+            Err("arithmetic overflow")
+        }
+    }
+}
+
+/// One-byte opcode tags used by the binary wire format.
+mod tag {
+    pub const LOAD_VAL: u8 = 0;
+    pub const WRITE_VAR: u8 = 1;
+    pub const READ_VAR: u8 = 2;
+    pub const ADD: u8 = 3;
+    pub const SUBTRACT: u8 = 4;
+    pub const MULTIPLY: u8 = 5;
+    pub const DIVIDE: u8 = 6;
+    pub const MODULO: u8 = 7;
+    pub const LOOP: u8 = 8;
+    pub const END_LOOP: u8 = 9;
+    pub const JUMP: u8 = 10;
+    pub const JUMP_IF_ZERO: u8 = 11;
+    pub const JUMP_IF_NON_ZERO: u8 = 12;
+    pub const EQUAL: u8 = 13;
+    pub const NOT_EQUAL: u8 = 14;
+    pub const LESS: u8 = 15;
+    pub const GREATER: u8 = 16;
+    pub const LESS_EQUAL: u8 = 17;
+    pub const GREATER_EQUAL: u8 = 18;
+    pub const AND: u8 = 19;
+    pub const OR: u8 = 20;
+    pub const NOT: u8 = 21;
+    pub const JUMP_IF_FALSE: u8 = 22;
+    pub const CALL: u8 = 23;
+    pub const RETURN: u8 = 24;
+
+    // Sub-tags for the operand of `LoadVal`.
+    pub const VALUE_INT: u8 = 0;
+    pub const VALUE_FLOAT: u8 = 1;
+    pub const VALUE_BOOL: u8 = 2;
+}
+
+/// Encode a program as a compact byte stream: a string table of variable names followed by the
+/// instructions, each a one-byte tag and varint-encoded (LEB128) operands; a `LoadVal` operand is
+/// a value sub-tag followed by its encoding (see [`encode_value`]). Repeated variable names
+/// are stored once and referenced by their string-table index.
+#[must_use]
+pub fn serialize(bytecodes: &[ByteCode]) -> Vec<u8> {
+    let mut names: Vec<&str> = Vec::new();
+    let mut index_of: HashMap<&str, u64> = HashMap::new();
+    for bytecode in bytecodes {
+        if let ByteCode::WriteVar(name) | ByteCode::ReadVar(name) = *bytecode {
+            index_of.entry(name).or_insert_with(|| {
+                names.push(name);
+                (names.len() - 1) as u64
+            });
+        }
+    }
+
+    let mut out = Vec::new();
+    write_uvarint(names.len() as u64, &mut out);
+    for name in &names {
+        write_uvarint(name.len() as u64, &mut out);
+        out.extend_from_slice(name.as_bytes());
+    }
+
+    write_uvarint(bytecodes.len() as u64, &mut out);
+    for bytecode in bytecodes {
+        encode_instruction(bytecode, &index_of, &mut out);
+    }
+    out
+}
+
+/// Decode a byte stream produced by [`serialize`] back into instructions, borrowing variable names
+/// from the input. Opcode tags, string-table indices and operand lengths are all validated so that
+/// truncated or malformed input is rejected rather than trusted.
+pub fn deserialize(input: &[u8]) -> Result<Vec<ByteCode<'_>>, &'static str> {
+    let mut position = 0;
+
+    // Every name occupies at least its one-byte length varint, so a count exceeding the bytes
+    // left cannot be honest; bound it before allocating to avoid a capacity-overflow panic on an
+    // attacker-controlled varint.
+    let name_count = read_bounded_count(input, &mut position, "String table is larger than input.")?;
+    let mut names: Vec<&str> = Vec::with_capacity(name_count);
+    for _ in 0..name_count {
+        let length = read_uvarint(input, &mut position)? as usize;
+        let end = position
+            .checked_add(length)
+            .ok_or("Truncated string table.")?;
+        let bytes = input.get(position..end).ok_or("Truncated string table.")?;
+        names.push(std::str::from_utf8(bytes).map_err(|_| "Invalid UTF-8 in string table.")?);
+        position = end;
+    }
+
+    // Likewise every instruction is at least a one-byte tag, so the count cannot exceed the bytes
+    // that remain.
+    let instruction_count =
+        read_bounded_count(input, &mut position, "Instruction count is larger than input.")?;
+    let mut bytecodes = Vec::with_capacity(instruction_count);
+    for _ in 0..instruction_count {
+        let opcode = *input.get(position).ok_or("Truncated bytecode.")?;
+        position += 1;
+        let bytecode = match opcode {
+            tag::LOAD_VAL => ByteCode::LoadVal(decode_value(input, &mut position)?),
+            tag::WRITE_VAR => ByteCode::WriteVar(read_name(&names, input, &mut position)?),
+            tag::READ_VAR => ByteCode::ReadVar(read_name(&names, input, &mut position)?),
+            tag::ADD => ByteCode::Add,
+            tag::SUBTRACT => ByteCode::Subtract,
+            tag::MULTIPLY => ByteCode::Multiply,
+            tag::DIVIDE => ByteCode::Divide,
+            tag::MODULO => ByteCode::Modulo,
+            tag::LOOP => ByteCode::Loop,
+            tag::END_LOOP => ByteCode::EndLoop,
+            tag::JUMP => ByteCode::Jump(read_uvarint(input, &mut position)? as usize),
+            tag::JUMP_IF_ZERO => ByteCode::JumpIfZero(read_uvarint(input, &mut position)? as usize),
+            tag::JUMP_IF_NON_ZERO => {
+                ByteCode::JumpIfNonZero(read_uvarint(input, &mut position)? as usize)
+            }
+            tag::EQUAL => ByteCode::Equal,
+            tag::NOT_EQUAL => ByteCode::NotEqual,
+            tag::LESS => ByteCode::Less,
+            tag::GREATER => ByteCode::Greater,
+            tag::LESS_EQUAL => ByteCode::LessEqual,
+            tag::GREATER_EQUAL => ByteCode::GreaterEqual,
+            tag::AND => ByteCode::And,
+            tag::OR => ByteCode::Or,
+            tag::NOT => ByteCode::Not,
+            tag::JUMP_IF_FALSE => ByteCode::JumpIfFalse(read_uvarint(input, &mut position)? as usize),
+            tag::CALL => ByteCode::Call {
+                target: read_uvarint(input, &mut position)? as usize,
+                arity: read_uvarint(input, &mut position)? as usize,
+            },
+            tag::RETURN => ByteCode::Return,
+            _ => return Err("Invalid opcode tag."),
+        };
+        bytecodes.push(bytecode);
+    }
+
+    Ok(bytecodes)
+}
+
+/// Render a program as human-readable assembly, each instruction prefixed by its byte offset in the
+/// instruction stream, e.g. `00000000  LOAD_VAL 1`, so a TA can inspect a submitted program.
+#[must_use]
+pub fn disassemble(bytecodes: &[ByteCode]) -> String {
+    let mut index_of: HashMap<&str, u64> = HashMap::new();
+    let mut next_index = 0;
+    for bytecode in bytecodes {
+        if let ByteCode::WriteVar(name) | ByteCode::ReadVar(name) = *bytecode {
+            index_of.entry(name).or_insert_with(|| {
+                let index = next_index;
+                next_index += 1;
+                index
+            });
+        }
+    }
+
+    let mut listing = String::new();
+    let mut offset = 0;
+    let mut scratch = Vec::new();
+    for bytecode in bytecodes {
+        scratch.clear();
+        encode_instruction(bytecode, &index_of, &mut scratch);
+        listing.push_str(&format!("{offset:08x}  {}\n", mnemonic(bytecode)));
+        offset += scratch.len();
+    }
+    listing
+}
+
+/// Encode a single instruction (tag plus operands) into `out`.
+fn encode_instruction(bytecode: &ByteCode, index_of: &HashMap<&str, u64>, out: &mut Vec<u8>) {
+    match *bytecode {
+        ByteCode::LoadVal(value) => {
+            out.push(tag::LOAD_VAL);
+            encode_value(value, out);
+        }
+        ByteCode::WriteVar(name) => {
+            out.push(tag::WRITE_VAR);
+            write_uvarint(index_of[name], out);
+        }
+        ByteCode::ReadVar(name) => {
+            out.push(tag::READ_VAR);
+            write_uvarint(index_of[name], out);
+        }
+        ByteCode::Add => out.push(tag::ADD),
+        ByteCode::Subtract => out.push(tag::SUBTRACT),
+        ByteCode::Multiply => out.push(tag::MULTIPLY),
+        ByteCode::Divide => out.push(tag::DIVIDE),
+        ByteCode::Modulo => out.push(tag::MODULO),
+        ByteCode::Loop => out.push(tag::LOOP),
+        ByteCode::EndLoop => out.push(tag::END_LOOP),
+        ByteCode::Jump(target) => {
+            out.push(tag::JUMP);
+            write_uvarint(target as u64, out);
+        }
+        ByteCode::JumpIfZero(target) => {
+            out.push(tag::JUMP_IF_ZERO);
+            write_uvarint(target as u64, out);
+        }
+        ByteCode::JumpIfNonZero(target) => {
+            out.push(tag::JUMP_IF_NON_ZERO);
+            write_uvarint(target as u64, out);
+        }
+        ByteCode::Equal => out.push(tag::EQUAL),
+        ByteCode::NotEqual => out.push(tag::NOT_EQUAL),
+        ByteCode::Less => out.push(tag::LESS),
+        ByteCode::Greater => out.push(tag::GREATER),
+        ByteCode::LessEqual => out.push(tag::LESS_EQUAL),
+        ByteCode::GreaterEqual => out.push(tag::GREATER_EQUAL),
+        ByteCode::And => out.push(tag::AND),
+        ByteCode::Or => out.push(tag::OR),
+        ByteCode::Not => out.push(tag::NOT),
+        ByteCode::JumpIfFalse(target) => {
+            out.push(tag::JUMP_IF_FALSE);
+            write_uvarint(target as u64, out);
+        }
+        ByteCode::Call { target, arity } => {
+            out.push(tag::CALL);
+            write_uvarint(target as u64, out);
+            write_uvarint(arity as u64, out);
+        }
+        ByteCode::Return => out.push(tag::RETURN),
+    }
+}
+
+/// The assembly mnemonic and operand of an instruction, as shown by [`disassemble`].
+fn mnemonic(bytecode: &ByteCode) -> String {
+    match *bytecode {
+        ByteCode::LoadVal(value) => format!("LOAD_VAL {}", render_value(value)),
+        ByteCode::WriteVar(name) => format!("WRITE_VAR {name}"),
+        ByteCode::ReadVar(name) => format!("READ_VAR {name}"),
+        ByteCode::Add => "ADD".to_string(),
+        ByteCode::Subtract => "SUBTRACT".to_string(),
+        ByteCode::Multiply => "MULTIPLY".to_string(),
+        ByteCode::Divide => "DIVIDE".to_string(),
+        ByteCode::Modulo => "MODULO".to_string(),
+        ByteCode::Loop => "LOOP".to_string(),
+        ByteCode::EndLoop => "END_LOOP".to_string(),
+        ByteCode::Jump(target) => format!("JUMP {target}"),
+        ByteCode::JumpIfZero(target) => format!("JUMP_IF_ZERO {target}"),
+        ByteCode::JumpIfNonZero(target) => format!("JUMP_IF_NON_ZERO {target}"),
+        ByteCode::Equal => "EQUAL".to_string(),
+        ByteCode::NotEqual => "NOT_EQUAL".to_string(),
+        ByteCode::Less => "LESS".to_string(),
+        ByteCode::Greater => "GREATER".to_string(),
+        ByteCode::LessEqual => "LESS_EQUAL".to_string(),
+        ByteCode::GreaterEqual => "GREATER_EQUAL".to_string(),
+        ByteCode::And => "AND".to_string(),
+        ByteCode::Or => "OR".to_string(),
+        ByteCode::Not => "NOT".to_string(),
+        ByteCode::JumpIfFalse(target) => format!("JUMP_IF_FALSE {target}"),
+        ByteCode::Call { target, arity } => format!("CALL {target} {arity}"),
+        ByteCode::Return => "RETURN".to_string(),
+    }
+}
+
+fn render_value(value: Value) -> String {
+    match value {
+        Value::Int(number) => number.to_string(),
+        Value::Float(number) => number.to_string(),
+        Value::Bool(boolean) => boolean.to_string(),
+    }
+}
+
+/// Encode a `LoadVal` operand: a sub-tag followed by a LEB128 integer, a little-endian `f64`, or a
+/// single boolean byte.
+fn encode_value(value: Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Int(number) => {
+            out.push(tag::VALUE_INT);
+            write_uvarint(number as u64, out);
+        }
+        Value::Float(number) => {
+            out.push(tag::VALUE_FLOAT);
+            out.extend_from_slice(&number.to_bits().to_le_bytes());
+        }
+        Value::Bool(boolean) => {
+            out.push(tag::VALUE_BOOL);
+            out.push(u8::from(boolean));
+        }
+    }
+}
+
+fn decode_value(input: &[u8], position: &mut usize) -> Result<Value, &'static str> {
+    let value_tag = *input.get(*position).ok_or("Truncated value operand.")?;
+    *position += 1;
+    match value_tag {
+        tag::VALUE_INT => Ok(Value::Int(read_uvarint(input, position)? as i64)),
+        tag::VALUE_FLOAT => {
+            let end = position.checked_add(8).ok_or("Truncated float operand.")?;
+            let bytes = input.get(*position..end).ok_or("Truncated float operand.")?;
+            let bits = u64::from_le_bytes(bytes.try_into().expect("checked an 8-byte window"));
+            *position = end;
+            Ok(Value::Float(f64::from_bits(bits)))
+        }
+        tag::VALUE_BOOL => {
+            let byte = *input.get(*position).ok_or("Truncated boolean operand.")?;
+            *position += 1;
+            Ok(Value::Bool(byte != 0))
+        }
+        _ => Err("Invalid value tag."),
+    }
+}
+
+/// Resolve a variable name operand through the string table, rejecting out-of-range indices.
+fn read_name<'a>(
+    names: &[&'a str],
+    input: &[u8],
+    position: &mut usize,
+) -> Result<&'a str, &'static str> {
+    let index = read_uvarint(input, position)? as usize;
+    names
+        .get(index)
+        .copied()
+        .ok_or("String table index out of range.")
+}
+
+/// Append `value` as an unsigned LEB128 varint.
+fn write_uvarint(mut value: u64, out: &mut Vec<u8>) {
+    loop {
+        let mut byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value != 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if value == 0 {
+            break;
+        }
+    }
+}
+
+/// Read a count varint and reject it when it exceeds the bytes left in `input`. Each counted item
+/// (a string or an instruction) costs at least one byte, so a larger count cannot be satisfied and
+/// must not be used to pre-allocate.
+fn read_bounded_count(
+    input: &[u8],
+    position: &mut usize,
+    message: &'static str,
+) -> Result<usize, &'static str> {
+    let count = read_uvarint(input, position)?;
+    let remaining = (input.len() - *position) as u64;
+    if count > remaining {
+        return Err(message);
+    }
+    Ok(count as usize)
+}
+
+/// Read an unsigned LEB128 varint, advancing `position` and rejecting truncated or oversized input.
+fn read_uvarint(input: &[u8], position: &mut usize) -> Result<u64, &'static str> {
+    let mut result = 0u64;
+    let mut shift = 0;
+    loop {
+        if shift >= 64 {
+            return Err("Varint is too long.");
+        }
+        let byte = *input.get(*position).ok_or("Truncated varint.")?;
+        *position += 1;
+        result |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
     }
+    Ok(result)
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{ByteCode, Interpreter};
+    use crate::{ByteCode, Interpreter, Value};
 
     #[test]
     fn problem_example_test() {
@@ -194,17 +925,17 @@ mod test {
         //    y = 2
         //    return (x + 1) * y
         let bytecodes = [
-            ByteCode::LoadVal(1),
+            ByteCode::LoadVal(Value::Int(1)),
             ByteCode::WriteVar("x"),
-            ByteCode::LoadVal(2),
+            ByteCode::LoadVal(Value::Int(2)),
             ByteCode::WriteVar("y"),
             ByteCode::ReadVar("x"),
-            ByteCode::LoadVal(1),
+            ByteCode::LoadVal(Value::Int(1)),
             ByteCode::Add,
             ByteCode::ReadVar("y"),
             ByteCode::Multiply,
         ];
-        assert_eq!(interpreter.evaluate(&bytecodes), Ok(4));
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(4)));
     }
 
     #[test]
@@ -215,15 +946,15 @@ mod test {
         let mut interpreter = Interpreter::new();
 
         let bytecodes = [
-            ByteCode::LoadVal(2),
+            ByteCode::LoadVal(Value::Int(2)),
             ByteCode::WriteVar("x"),
-            ByteCode::LoadVal(3),
+            ByteCode::LoadVal(Value::Int(3)),
             ByteCode::WriteVar("y"),
             ByteCode::ReadVar("x"),
             ByteCode::ReadVar("y"),
             ByteCode::Add,
         ];
-        assert_eq!(interpreter.evaluate(&bytecodes), Ok(5));
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(5)));
     }
 
     #[test]
@@ -235,15 +966,15 @@ mod test {
         let mut interpreter = Interpreter::new();
 
         let bytecodes = [
-            ByteCode::LoadVal(2),
+            ByteCode::LoadVal(Value::Int(2)),
             ByteCode::WriteVar("x"),
-            ByteCode::LoadVal(3),
+            ByteCode::LoadVal(Value::Int(3)),
             ByteCode::WriteVar("y"),
             ByteCode::ReadVar("x"),
             ByteCode::ReadVar("y"),
             ByteCode::Subtract,
         ];
-        assert_eq!(interpreter.evaluate(&bytecodes), Ok(-1));
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(-1)));
     }
 
     #[test]
@@ -255,15 +986,15 @@ mod test {
         let mut interpreter = Interpreter::new();
 
         let bytecodes = [
-            ByteCode::LoadVal(10),
+            ByteCode::LoadVal(Value::Int(10)),
             ByteCode::WriteVar("x"),
-            ByteCode::LoadVal(5),
+            ByteCode::LoadVal(Value::Int(5)),
             ByteCode::WriteVar("y"),
             ByteCode::ReadVar("x"),
             ByteCode::ReadVar("y"),
             ByteCode::Divide,
         ];
-        assert_eq!(interpreter.evaluate(&bytecodes), Ok(2));
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(2)));
     }
 
     #[test]
@@ -274,15 +1005,15 @@ mod test {
         let mut interpreter = Interpreter::new();
 
         let bytecodes = [
-            ByteCode::LoadVal(10),
+            ByteCode::LoadVal(Value::Int(10)),
             ByteCode::WriteVar("x"),
-            ByteCode::LoadVal(5),
+            ByteCode::LoadVal(Value::Int(5)),
             ByteCode::WriteVar("y"),
             ByteCode::ReadVar("x"),
             ByteCode::ReadVar("y"),
             ByteCode::Multiply,
         ];
-        assert_eq!(interpreter.evaluate(&bytecodes), Ok(50));
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(50)));
     }
 
     #[test]
@@ -293,15 +1024,15 @@ mod test {
         let mut interpreter = Interpreter::new();
 
         let bytecodes = [
-            ByteCode::LoadVal(10),
+            ByteCode::LoadVal(Value::Int(10)),
             ByteCode::WriteVar("x"),
-            ByteCode::LoadVal(5),
+            ByteCode::LoadVal(Value::Int(5)),
             ByteCode::WriteVar("y"),
             ByteCode::ReadVar("x"),
             ByteCode::ReadVar("y"),
             ByteCode::Modulo,
         ];
-        assert_eq!(interpreter.evaluate(&bytecodes), Ok(0));
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(0)));
     }
 
     #[test]
@@ -313,13 +1044,13 @@ mod test {
         let mut interpreter = Interpreter::new();
 
         let bytecodes = [
-            ByteCode::LoadVal(10),
+            ByteCode::LoadVal(Value::Int(10)),
             ByteCode::WriteVar("x"),
-            ByteCode::LoadVal(5),
+            ByteCode::LoadVal(Value::Int(5)),
             ByteCode::WriteVar("x"),
             ByteCode::ReadVar("x"),
         ];
-        assert_eq!(interpreter.evaluate(&bytecodes), Ok(5));
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(5)));
     }
 
     #[test]
@@ -330,15 +1061,15 @@ mod test {
         let mut interpreter = Interpreter::new();
 
         let bytecodes = [
-            ByteCode::LoadVal(10),
+            ByteCode::LoadVal(Value::Int(10)),
             ByteCode::WriteVar("x"),
-            ByteCode::LoadVal(5),
+            ByteCode::LoadVal(Value::Int(5)),
             ByteCode::ReadVar("x"),
             ByteCode::Add,
             ByteCode::WriteVar("x"),
             ByteCode::ReadVar("x"),
         ];
-        assert_eq!(interpreter.evaluate(&bytecodes), Ok(15));
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(15)));
     }
 
     #[test]
@@ -352,25 +1083,25 @@ mod test {
         let mut interpreter = Interpreter::new();
 
         let bytecodes = [
-            ByteCode::LoadVal(10),
+            ByteCode::LoadVal(Value::Int(10)),
             ByteCode::WriteVar("x"),
-            ByteCode::LoadVal(5),
+            ByteCode::LoadVal(Value::Int(5)),
             ByteCode::ReadVar("x"),
             ByteCode::Add,
             ByteCode::WriteVar("x"),
-            ByteCode::LoadVal(4),
+            ByteCode::LoadVal(Value::Int(4)),
             ByteCode::WriteVar("z"),
-            ByteCode::LoadVal(15),
+            ByteCode::LoadVal(Value::Int(15)),
             ByteCode::WriteVar("cacao"),
             ByteCode::ReadVar("x"),
-            ByteCode::LoadVal(5),
+            ByteCode::LoadVal(Value::Int(5)),
             ByteCode::Multiply,
             ByteCode::ReadVar("cacao"),
             ByteCode::Divide,
             ByteCode::ReadVar("z"),
             ByteCode::Subtract,
         ];
-        assert_eq!(interpreter.evaluate(&bytecodes), Ok(1));
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(1)));
     }
 
     #[test]
@@ -382,18 +1113,336 @@ mod test {
         //    endloop
         //    return x
         let bytecodes = [
-            ByteCode::LoadVal(0),
+            ByteCode::LoadVal(Value::Int(0)),
             ByteCode::WriteVar("x"),
-            ByteCode::LoadVal(5),
+            ByteCode::LoadVal(Value::Int(5)),
             ByteCode::Loop,
             ByteCode::ReadVar("x"),
-            ByteCode::LoadVal(5),
+            ByteCode::LoadVal(Value::Int(5)),
             ByteCode::Add,
             ByteCode::WriteVar("x"),
             ByteCode::EndLoop,
             ByteCode::ReadVar("x"),
         ];
-        assert_eq!(interpreter.evaluate(&bytecodes), Ok(30));
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(25)));
+    }
+
+    #[test]
+    fn conditional_jump_taken() {
+        let mut interpreter = Interpreter::new();
+        //    if 0 { return 1 } else { return 2 }
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(0)),
+            ByteCode::JumpIfZero(4),
+            ByteCode::LoadVal(Value::Int(1)),
+            ByteCode::Jump(5),
+            ByteCode::LoadVal(Value::Int(2)),
+        ];
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(2)));
+    }
+
+    #[test]
+    fn conditional_jump_not_taken() {
+        let mut interpreter = Interpreter::new();
+        //    if 7 != 0 { return 1 } else { return 2 }
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(7)),
+            ByteCode::JumpIfZero(4),
+            ByteCode::LoadVal(Value::Int(1)),
+            ByteCode::Jump(5),
+            ByteCode::LoadVal(Value::Int(2)),
+        ];
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(1)));
+    }
+
+    #[test]
+    fn comparison_pushes_boolean() {
+        let mut interpreter = Interpreter::new();
+        //    return 2 < 5
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(2)),
+            ByteCode::LoadVal(Value::Int(5)),
+            ByteCode::Less,
+        ];
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn large_integer_comparison_is_exact() {
+        let mut interpreter = Interpreter::new();
+        //    return i64::MAX == i64::MAX - 1  (must stay false, no f64 rounding)
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(i64::MAX)),
+            ByteCode::LoadVal(Value::Int(i64::MAX - 1)),
+            ByteCode::Equal,
+        ];
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Bool(false)));
+    }
+
+    #[test]
+    fn logical_and_or_not() {
+        let mut interpreter = Interpreter::new();
+        //    return (!(1 == 1) || (3 >= 3)) && (2 < 5)
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(1)),
+            ByteCode::LoadVal(Value::Int(1)),
+            ByteCode::Equal,
+            ByteCode::Not,
+            ByteCode::LoadVal(Value::Int(3)),
+            ByteCode::LoadVal(Value::Int(3)),
+            ByteCode::GreaterEqual,
+            ByteCode::Or,
+            ByteCode::LoadVal(Value::Int(2)),
+            ByteCode::LoadVal(Value::Int(5)),
+            ByteCode::Less,
+            ByteCode::And,
+        ];
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn jump_if_false_branches_on_boolean() {
+        let mut interpreter = Interpreter::new();
+        //    if 5 == 4 { return 1 } else { return 2 }
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(5)),
+            ByteCode::LoadVal(Value::Int(4)),
+            ByteCode::Equal,
+            ByteCode::JumpIfFalse(6),
+            ByteCode::LoadVal(Value::Int(1)),
+            ByteCode::Jump(7),
+            ByteCode::LoadVal(Value::Int(2)),
+        ];
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(2)));
+    }
+
+    #[test]
+    fn comparison_not_enough_operands_error() {
+        let mut interpreter = Interpreter::new();
+        let bytecodes = [ByteCode::LoadVal(Value::Int(5)), ByteCode::Less];
+
+        assert_eq!(
+            interpreter.evaluate(&bytecodes),
+            Err("Incorrectly formatted expression: expecting 2 operands.")
+        );
+    }
+
+    #[test]
+    fn float_operand_promotes_result() {
+        let mut interpreter = Interpreter::new();
+        //    return 1 + 2.5
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(1)),
+            ByteCode::LoadVal(Value::Float(2.5)),
+            ByteCode::Add,
+        ];
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Float(3.5)));
+    }
+
+    #[test]
+    fn integer_division_stays_integer() {
+        let mut interpreter = Interpreter::new();
+        //    return 7 / 2
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(7)),
+            ByteCode::LoadVal(Value::Int(2)),
+            ByteCode::Divide,
+        ];
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(3)));
+    }
+
+    #[test]
+    fn load_int_helper_matches_load_val() {
+        let mut interpreter = Interpreter::new();
+        //    return 2 + 3, written with the load_int convenience constructor
+        let bytecodes = [
+            ByteCode::load_int(2),
+            ByteCode::load_int(3),
+            ByteCode::Add,
+        ];
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(5)));
+    }
+
+    #[test]
+    fn integer_overflow_error() {
+        let mut interpreter = Interpreter::new();
+        //    return i64::MAX + 1
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(i64::MAX)),
+            ByteCode::LoadVal(Value::Int(1)),
+            ByteCode::Add,
+        ];
+        assert_eq!(
+            interpreter.evaluate(&bytecodes),
+            Err("arithmetic overflow")
+        );
+    }
+
+    #[test]
+    fn divide_by_zero_error() {
+        let mut interpreter = Interpreter::new();
+        //    return 1 / 0
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(1)),
+            ByteCode::LoadVal(Value::Int(0)),
+            ByteCode::Divide,
+        ];
+        assert_eq!(
+            interpreter.evaluate(&bytecodes),
+            Err("Attempted to divide by zero.")
+        );
+    }
+
+    #[test]
+    fn call_and_return_a_function() {
+        let mut interpreter = Interpreter::new();
+        //    fn double(n) { return n + n }
+        //    return double(5)
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(5)),
+            ByteCode::Call { target: 4, arity: 1 },
+            ByteCode::Jump(9),
+            ByteCode::LoadVal(Value::Int(0)), // padding: the caller jumps over the function body
+            ByteCode::WriteVar("n"),
+            ByteCode::ReadVar("n"),
+            ByteCode::ReadVar("n"),
+            ByteCode::Add,
+            ByteCode::Return,
+        ];
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(10)));
+    }
+
+    #[test]
+    fn call_transfers_arguments_and_isolates_the_caller_stack() {
+        let mut interpreter = Interpreter::new();
+        //    fn add(a, b) { return a + b }
+        //    99                 -- stays on the caller stack, invisible to the callee
+        //    return add(7, 8)
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(99)),
+            ByteCode::LoadVal(Value::Int(7)),
+            ByteCode::LoadVal(Value::Int(8)),
+            ByteCode::Call { target: 6, arity: 2 },
+            ByteCode::Jump(12),
+            ByteCode::LoadVal(Value::Int(0)), // padding: the caller jumps over the function body
+            ByteCode::WriteVar("b"),
+            ByteCode::WriteVar("a"),
+            ByteCode::ReadVar("a"),
+            ByteCode::ReadVar("b"),
+            ByteCode::Add,
+            ByteCode::Return,
+        ];
+        // The callee only saw 7 and 8; its result 15 lands on top of the untouched 99.
+        assert_eq!(interpreter.evaluate(&bytecodes), Ok(Value::Int(15)));
+    }
+
+    #[test]
+    fn call_with_too_few_arguments_error() {
+        let mut interpreter = Interpreter::new();
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(1)),
+            ByteCode::Call { target: 3, arity: 2 },
+            ByteCode::Jump(4),
+            ByteCode::Return,
+        ];
+        assert_eq!(
+            interpreter.evaluate(&bytecodes),
+            Err("Not enough arguments for Call.")
+        );
+    }
+
+    #[test]
+    fn callee_cannot_see_caller_variables() {
+        let mut interpreter = Interpreter::new();
+        //    x = 1
+        //    call f  -- f reads x, which only exists in the caller frame
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(1)),
+            ByteCode::WriteVar("x"),
+            ByteCode::Call { target: 4, arity: 0 },
+            ByteCode::Jump(6),
+            ByteCode::ReadVar("x"),
+            ByteCode::Return,
+        ];
+        assert_eq!(
+            interpreter.evaluate(&bytecodes),
+            Err("Trying to read variable that does not exist.")
+        );
+    }
+
+    #[test]
+    fn return_without_call_error() {
+        let mut interpreter = Interpreter::new();
+        let bytecodes = [ByteCode::LoadVal(Value::Int(1)), ByteCode::Return];
+
+        assert_eq!(
+            interpreter.evaluate(&bytecodes),
+            Err("Return without a matching Call.")
+        );
+    }
+
+    #[test]
+    fn fuel_exhaustion_error() {
+        let mut interpreter = Interpreter::with_fuel(3);
+        //    x = 0
+        //    loop 100
+        //      x = x + 1
+        //    endloop
+        //    return x
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(0)),
+            ByteCode::WriteVar("x"),
+            ByteCode::LoadVal(Value::Int(100)),
+            ByteCode::Loop,
+            ByteCode::ReadVar("x"),
+            ByteCode::LoadVal(Value::Int(1)),
+            ByteCode::Add,
+            ByteCode::WriteVar("x"),
+            ByteCode::EndLoop,
+            ByteCode::ReadVar("x"),
+        ];
+        assert_eq!(
+            interpreter.evaluate(&bytecodes),
+            Err("execution budget exceeded")
+        );
+    }
+
+    #[test]
+    fn stack_depth_limit_error() {
+        let mut interpreter = Interpreter::new();
+        //    loop 2000 { push 1 }  -- never popped, blows the stack cap
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(2000)),
+            ByteCode::Loop,
+            ByteCode::LoadVal(Value::Int(1)),
+            ByteCode::EndLoop,
+        ];
+        assert_eq!(
+            interpreter.evaluate(&bytecodes),
+            Err("maximum stack depth exceeded")
+        );
+    }
+
+    #[test]
+    fn conditional_jump_on_boolean_error() {
+        let mut interpreter = Interpreter::new();
+        let bytecodes = [ByteCode::LoadVal(Value::Bool(false)), ByteCode::JumpIfZero(3)];
+
+        assert_eq!(
+            interpreter.evaluate(&bytecodes),
+            Err("Cannot branch on a boolean with JumpIfZero/JumpIfNonZero.")
+        );
+    }
+
+    #[test]
+    fn out_of_bounds_jump_error() {
+        let mut interpreter = Interpreter::new();
+        let bytecodes = [ByteCode::LoadVal(Value::Int(1)), ByteCode::Jump(99)];
+
+        assert_eq!(
+            interpreter.evaluate(&bytecodes),
+            Err("Jump target is out of bounds.")
+        );
     }
 
     #[test]
@@ -438,11 +1487,11 @@ mod test {
         //    endloop
         //    return x
         let bytecodes = [
-            ByteCode::LoadVal(0),
+            ByteCode::LoadVal(Value::Int(0)),
             ByteCode::WriteVar("x"),
             ByteCode::Loop,
             ByteCode::ReadVar("x"),
-            ByteCode::LoadVal(5),
+            ByteCode::LoadVal(Value::Int(5)),
             ByteCode::Add,
             ByteCode::WriteVar("x"),
             ByteCode::EndLoop,
@@ -463,12 +1512,12 @@ mod test {
         //
         //    return x
         let bytecodes = [
-            ByteCode::LoadVal(0),
+            ByteCode::LoadVal(Value::Int(0)),
             ByteCode::WriteVar("x"),
-            ByteCode::LoadVal(5),
+            ByteCode::LoadVal(Value::Int(5)),
             ByteCode::Loop,
             ByteCode::ReadVar("x"),
-            ByteCode::LoadVal(5),
+            ByteCode::LoadVal(Value::Int(5)),
             ByteCode::Add,
             ByteCode::WriteVar("x"),
             ByteCode::ReadVar("x"),
@@ -479,11 +1528,91 @@ mod test {
             Err("There is no EndLoop instruction associated to the previous Loop.")
         );
     }
+    #[test]
+    fn serialize_round_trips() {
+        use crate::{deserialize, serialize};
+
+        let bytecodes = [
+            ByteCode::LoadVal(Value::Int(1)),
+            ByteCode::WriteVar("x"),
+            ByteCode::LoadVal(Value::Float(2.5)),
+            ByteCode::WriteVar("y"),
+            ByteCode::ReadVar("x"),
+            ByteCode::ReadVar("y"),
+            ByteCode::Add,
+            ByteCode::LoadVal(Value::Bool(true)),
+            ByteCode::Jump(9),
+            ByteCode::Call { target: 3, arity: 2 },
+            ByteCode::Return,
+        ];
+
+        let encoded = serialize(&bytecodes);
+        assert_eq!(deserialize(&encoded), Ok(bytecodes.to_vec()));
+    }
+
+    #[test]
+    fn disassemble_prefixes_byte_offsets() {
+        use crate::disassemble;
+
+        let bytecodes = [ByteCode::LoadVal(Value::Int(1)), ByteCode::WriteVar("x")];
+
+        assert_eq!(
+            disassemble(&bytecodes),
+            "00000000  LOAD_VAL 1\n00000003  WRITE_VAR x\n"
+        );
+    }
+
+    #[test]
+    fn deserialize_rejects_unknown_opcode() {
+        use crate::deserialize;
+
+        // No string table, one instruction, opcode tag 250 does not exist.
+        let encoded = [0x00, 0x01, 250];
+
+        assert_eq!(deserialize(&encoded), Err("Invalid opcode tag."));
+    }
+
+    #[test]
+    fn deserialize_rejects_truncated_operand() {
+        use crate::deserialize;
+
+        // No string table, one instruction, `Jump` with its varint operand missing.
+        let encoded = [0x00, 0x01, 10];
+
+        assert_eq!(deserialize(&encoded), Err("Truncated varint."));
+    }
+
+    #[test]
+    fn deserialize_rejects_oversized_count() {
+        use crate::deserialize;
+
+        // A ~2^63 name count that would panic `Vec::with_capacity` if trusted.
+        let encoded = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f];
+
+        assert_eq!(
+            deserialize(&encoded),
+            Err("String table is larger than input.")
+        );
+    }
+
+    #[test]
+    fn deserialize_rejects_out_of_range_name() {
+        use crate::deserialize;
+
+        // No string table, one `WriteVar` referencing string-table index 0.
+        let encoded = [0x00, 0x01, 1, 0];
+
+        assert_eq!(
+            deserialize(&encoded),
+            Err("String table index out of range.")
+        );
+    }
+
     #[test]
     fn not_enough_operands_in_binary_operation_error() {
         let mut interpreter = Interpreter::new();
         //    return 5 +
-        let bytecodes = [ByteCode::LoadVal(5), ByteCode::Add];
+        let bytecodes = [ByteCode::LoadVal(Value::Int(5)), ByteCode::Add];
 
         assert_eq!(
             interpreter.evaluate(&bytecodes),